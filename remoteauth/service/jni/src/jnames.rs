@@ -0,0 +1,25 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Java method names/signatures resolved once at `JavaPlatform::new` time and cached as
+//! `JMethodID`s, so the hot path never calls `get_method_id`.
+
+/// Name of the Java-side `NativeRemoteAuthJavaPlatform.sendRequest` method.
+pub const SEND_REQUEST_MNAME: &str = "sendRequest";
+/// JNI signature of `sendRequest(int connectionId, Request request, long responseHandle, long
+/// platformHandle)`. Takes the typed `Request` object built by [`ToJavaObject`], not a raw `[B`
+/// payload — keep this in sync with the constructed arguments in `JavaPlatform::send_request`.
+///
+/// [`ToJavaObject`]: crate::object_mapping::ToJavaObject
+pub const SEND_REQUEST_MSIG: &str = "(ILcom/android/server/remoteauth/jni/Request;JJ)V";
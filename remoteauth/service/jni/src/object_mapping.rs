@@ -0,0 +1,219 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conversions between the remoteauth protocol's typed Rust messages and their Java object
+//! representation, so JNI entry points can deal in `Request`/`Response` instead of opaque
+//! byte slices.
+use anyhow::anyhow;
+use jni::objects::{GlobalRef, JMap, JMethodID, JObject, JString, JValue};
+use jni::signature::{Primitive, ReturnType};
+use jni::sys::jvalue;
+use jni::JNIEnv;
+use std::collections::HashMap;
+
+/// Fully-qualified Java class names backing the types in this module.
+pub const REQUEST_CLASS: &str = "com/android/server/remoteauth/jni/Request";
+pub const RESPONSE_CLASS: &str = "com/android/server/remoteauth/jni/Response";
+pub const HASH_MAP_CLASS: &str = "java/util/HashMap";
+
+/// String key/value metadata attached to a request or response, e.g. `content-type`,
+/// `request-id`, or connection hints, mirrored to/from a Java `Map<String, String>`.
+pub type HeaderMap = HashMap<String, String>;
+
+/// Constructor/getter `JMethodID`s for [`Request`]/[`Response`], resolved once at
+/// `JavaPlatform::new` time and cached so the hot path never calls `get_method_id`.
+pub struct ObjectMappingMethodIds {
+    pub hash_map_class: GlobalRef,
+    pub hash_map_ctor: JMethodID,
+    pub request_class: GlobalRef,
+    pub request_ctor: JMethodID,
+    pub response_get_status_code: JMethodID,
+    pub response_get_headers: JMethodID,
+    pub response_get_payload: JMethodID,
+}
+
+/// Converts a Rust value into its Java object representation.
+pub trait ToJavaObject {
+    /// Builds the Java object equivalent of `self` in `env`, using the cached method ids.
+    fn to_java_object<'e>(
+        &self,
+        env: &JNIEnv<'e>,
+        method_ids: &ObjectMappingMethodIds,
+    ) -> anyhow::Result<JObject<'e>>;
+}
+
+/// Reconstructs a Rust value from its Java object representation.
+pub trait FromJavaObject: Sized {
+    /// Reads `obj`'s fields back into a Rust value, using the cached method ids.
+    fn from_java_object(
+        env: &JNIEnv,
+        obj: JObject,
+        method_ids: &ObjectMappingMethodIds,
+    ) -> anyhow::Result<Self>;
+}
+
+/// The header of a [`Request`]: its correlation id plus its metadata. The payload is carried
+/// separately, in [`Request::payload`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestHeader {
+    /// Caller-assigned id used to correlate this request with its response.
+    pub request_id: i64,
+    /// Extensible metadata for the request, e.g. `content-type`.
+    pub headers: HeaderMap,
+}
+
+/// A typed request, mirroring the Java `Request` object built for the `send_request` upcall.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub header: RequestHeader,
+    pub payload: Vec<u8>,
+}
+
+/// The status of a completed request, mirrored from the Java `Response` object. Modeled as an
+/// HTTP-like status code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusCode(pub u16);
+
+/// A typed response from the remote device: a status code, its headers, and the payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub payload: Vec<u8>,
+}
+
+/// Builds the Java `HashMap<String, String>` equivalent of `headers`.
+fn headers_to_java_map<'e>(
+    env: &JNIEnv<'e>,
+    method_ids: &ObjectMappingMethodIds,
+    headers: &HeaderMap,
+) -> anyhow::Result<JObject<'e>> {
+    let map_jobject = env.new_object_unchecked(
+        method_ids.hash_map_class.as_obj(),
+        method_ids.hash_map_ctor,
+        &[],
+    )?;
+    let map = JMap::from_env(env, map_jobject)?;
+    for (key, value) in headers {
+        let key_jstring = env.new_string(key)?;
+        let value_jstring = env.new_string(value)?;
+        map.put(key_jstring.into(), value_jstring.into())?;
+    }
+    Ok(map_jobject)
+}
+
+/// Reads a Java `Map<String, String>` back into a [`HeaderMap`].
+fn java_map_to_headers(env: &JNIEnv, map_jobject: JObject) -> anyhow::Result<HeaderMap> {
+    let map = JMap::from_env(env, map_jobject)?;
+    let mut headers = HeaderMap::new();
+    for (key, value) in map.iter()? {
+        let key: String = env.get_string(JString::from(key))?.into();
+        let value: String = env.get_string(JString::from(value))?.into();
+        headers.insert(key, value);
+    }
+    Ok(headers)
+}
+
+impl ToJavaObject for Request {
+    fn to_java_object<'e>(
+        &self,
+        env: &JNIEnv<'e>,
+        method_ids: &ObjectMappingMethodIds,
+    ) -> anyhow::Result<JObject<'e>> {
+        let headers_jobject = headers_to_java_map(env, method_ids, &self.header.headers)?;
+        let payload_jbytearray = env.byte_array_from_slice(&self.payload)?;
+        // Safety: payload_jbytearray is safely instantiated above.
+        let payload_jobject = unsafe { JObject::from_raw(payload_jbytearray) };
+        let request_jobject = env.new_object_unchecked(
+            method_ids.request_class.as_obj(),
+            method_ids.request_ctor,
+            &[
+                jvalue::from(JValue::Long(self.header.request_id)),
+                jvalue::from(JValue::Object(headers_jobject)),
+                jvalue::from(JValue::Object(payload_jobject)),
+            ],
+        )?;
+        Ok(request_jobject)
+    }
+}
+
+impl FromJavaObject for Response {
+    fn from_java_object(
+        env: &JNIEnv,
+        obj: JObject,
+        method_ids: &ObjectMappingMethodIds,
+    ) -> anyhow::Result<Self> {
+        let status = env
+            .call_method_unchecked(
+                obj,
+                method_ids.response_get_status_code,
+                ReturnType::Primitive(Primitive::Int),
+                &[],
+            )?
+            .i()
+            .map_err(|e| anyhow!("JNI: Failed to read Response.statusCode: {:?}", e))?;
+        let headers_jobject = env
+            .call_method_unchecked(
+                obj,
+                method_ids.response_get_headers,
+                ReturnType::Object,
+                &[],
+            )?
+            .l()
+            .map_err(|e| anyhow!("JNI: Failed to read Response.headers: {:?}", e))?;
+        let headers = java_map_to_headers(env, headers_jobject)?;
+        let payload_jobject = env
+            .call_method_unchecked(obj, method_ids.response_get_payload, ReturnType::Array, &[])?
+            .l()
+            .map_err(|e| anyhow!("JNI: Failed to read Response.payload: {:?}", e))?;
+        let payload = env.convert_byte_array(payload_jobject.into_raw())?;
+        Ok(Response {
+            status: status_code_from_java(status)?,
+            headers,
+            payload,
+        })
+    }
+}
+
+/// Validates a status code read back from `Response.getStatusCode()`, rejecting values outside
+/// `u16` range instead of silently truncating them.
+///
+/// Split out of [`Response::from_java_object`] as a plain function over just the `jint` value so
+/// this range check can be tested without a live `JNIEnv`.
+fn status_code_from_java(status: i32) -> anyhow::Result<StatusCode> {
+    u16::try_from(status)
+        .map(StatusCode)
+        .map_err(|_| anyhow!("JNI: Response.statusCode {} is out of range for u16", status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An in-range status code should round-trip into the matching `StatusCode`.
+    #[test]
+    fn test_status_code_from_java_in_range() {
+        assert_eq!(status_code_from_java(200).unwrap(), StatusCode(200));
+        assert_eq!(status_code_from_java(0).unwrap(), StatusCode(0));
+        assert_eq!(status_code_from_java(u16::MAX as i32).unwrap(), StatusCode(u16::MAX));
+    }
+
+    /// A status code outside `u16` range should be rejected rather than truncated.
+    #[test]
+    fn test_status_code_from_java_rejects_out_of_range() {
+        assert!(status_code_from_java(-1).is_err());
+        assert!(status_code_from_java(u16::MAX as i32 + 1).is_err());
+        assert!(status_code_from_java(i32::MAX).is_err());
+    }
+}